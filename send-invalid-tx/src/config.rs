@@ -0,0 +1,98 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+
+/// Optional settings loaded from a `--config` file. `kms_port` and
+/// `controller_port` are overridden by their matching CLI flags when
+/// present; the remaining fields have no CLI flag on `run`/`bench` and
+/// fall back straight to the built-in default in [`TxParams::default`]
+/// when left unset here.
+#[derive(Deserialize, Default)]
+pub struct Config {
+    pub kms_port: Option<String>,
+    pub controller_port: Option<String>,
+    /// Hex-encoded chain id, e.g. `"0x00..00"`.
+    pub chain_id: Option<String>,
+    pub quota: Option<u64>,
+    pub version: Option<u32>,
+    /// Number of blocks a tx stays valid for after `start_block_number`.
+    pub valid_until_block_window: Option<u64>,
+}
+
+/// Resolved transaction-building parameters, merged from an optional
+/// config file on top of the built-in defaults. No CLI flag feeds into
+/// them; `send` applies its own `--quota`/`--valid-until-block` per tx.
+pub struct TxParams {
+    pub chain_id: Vec<u8>,
+    pub quota: u64,
+    pub version: u32,
+    pub valid_until_block_window: u64,
+}
+
+impl Default for TxParams {
+    fn default() -> Self {
+        TxParams {
+            chain_id: vec![0u8; 32],
+            quota: 300_000,
+            version: 0,
+            valid_until_block_window: 80,
+        }
+    }
+}
+
+pub fn load_config(path: &str) -> Config {
+    let content = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("failed to read config file {}: {}", path, e));
+    if path.ends_with(".yaml") || path.ends_with(".yml") {
+        serde_yaml::from_str(&content)
+            .unwrap_or_else(|e| panic!("failed to parse config file {}: {}", path, e))
+    } else {
+        toml::from_str(&content)
+            .unwrap_or_else(|e| panic!("failed to parse config file {}: {}", path, e))
+    }
+}
+
+fn parse_hex_chain_id(s: &str) -> Vec<u8> {
+    hex::decode(s.trim_start_matches("0x")).expect("chain_id is not valid hex")
+}
+
+impl TxParams {
+    /// Merge an optional config file on top of the built-in defaults.
+    pub fn from_config(config: Option<&Config>) -> Self {
+        let defaults = TxParams::default();
+        let config = match config {
+            Some(config) => config,
+            None => return defaults,
+        };
+        TxParams {
+            chain_id: config
+                .chain_id
+                .as_deref()
+                .map(parse_hex_chain_id)
+                .unwrap_or(defaults.chain_id),
+            quota: config.quota.unwrap_or(defaults.quota),
+            version: config.version.unwrap_or(defaults.version),
+            valid_until_block_window: config
+                .valid_until_block_window
+                .unwrap_or(defaults.valid_until_block_window),
+        }
+    }
+}
+
+/// Resolve a value that may come from a CLI flag or a config file lookup,
+/// falling back to `default` when neither is set.
+pub fn resolve(cli: Option<String>, from_config: Option<String>, default: &str) -> String {
+    cli.or(from_config).unwrap_or_else(|| default.to_owned())
+}
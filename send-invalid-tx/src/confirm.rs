@@ -0,0 +1,76 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use cita_ng_proto::blockchain::Hash;
+use cita_ng_proto::controller::{rpc_service_client::RpcServiceClient, Flag};
+use std::time::Duration;
+use tonic::transport::Channel;
+use tonic::Request;
+
+/// Where a submitted transaction ended up, once `wait_for_confirmation`
+/// has seen it included (and, if requested, followed by enough further
+/// blocks).
+pub struct TxReceipt {
+    pub block_number: u64,
+}
+
+/// Poll the controller for `tx_hash` until it shows up in a block and
+/// `confirmations` further blocks have been mined on top of it, or
+/// `timeout` elapses - whichever comes first. Returns `None` on timeout.
+pub async fn wait_for_confirmation(
+    rpc_client: &mut RpcServiceClient<Channel>,
+    tx_hash: Vec<u8>,
+    confirmations: u64,
+    poll_interval: Duration,
+    timeout: Duration,
+) -> Option<TxReceipt> {
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    let included_block = loop {
+        let request = Request::new(Hash {
+            hash: tx_hash.clone(),
+        });
+        // A successful response only means the controller knows about the
+        // tx - it may still be sitting in the pending pool. `block_number`
+        // is 0 until it has actually been packed into a block, so that's
+        // the signal we wait on rather than `Result::is_ok()` alone.
+        if let Ok(response) = rpc_client.get_transaction(request).await {
+            let block_number = response.into_inner().block_number;
+            if block_number != 0 {
+                break block_number;
+            }
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return None;
+        }
+        tokio::time::sleep(poll_interval).await;
+    };
+
+    while confirmations > 0 {
+        let request = Request::new(Flag { flag: false });
+        if let Ok(response) = rpc_client.get_block_number(request).await {
+            if response.into_inner().block_number >= included_block + confirmations {
+                break;
+            }
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return None;
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+
+    Some(TxReceipt {
+        block_number: included_block,
+    })
+}
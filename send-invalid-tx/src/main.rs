@@ -17,6 +17,13 @@ use git_version::git_version;
 use log::info;
 use tokio::runtime::Runtime;
 
+mod config;
+mod confirm;
+mod signer;
+use config::{load_config, resolve, Config, TxParams};
+use confirm::wait_for_confirmation;
+use signer::{KmsSigner, LocalSigner, Signer};
+
 const GIT_VERSION: &str = git_version!(
     args = ["--tags", "--always", "--dirty=-modified"],
     fallback = "unknown"
@@ -39,17 +46,121 @@ enum SubCommand {
     /// run this service
     #[clap(name = "run")]
     Run(RunOpts),
+    /// generate load against a running node and report throughput/latency
+    #[clap(name = "bench")]
+    Bench(BenchOpts),
+    /// sign and submit a single arbitrary transaction
+    #[clap(name = "send")]
+    Send(SendOpts),
 }
 
 /// A subcommand for run
 #[derive(Clap)]
 struct RunOpts {
     /// Sets grpc port of kms service.
-    #[clap(short = "k", long = "kms_port", default_value = "50005")]
-    kms_port: String,
+    #[clap(short = "k", long = "kms_port")]
+    kms_port: Option<String>,
+    /// Sets grpc port of controller service.
+    #[clap(short = "c", long = "controller_port")]
+    controller_port: Option<String>,
+    /// Sets path of a TOML/YAML config file with ports, chain_id and
+    /// signing parameters. CLI flags take precedence over the file.
+    #[clap(long = "config")]
+    config: Option<String>,
+}
+
+/// A subcommand for bench
+#[derive(Clap)]
+struct BenchOpts {
+    /// Sets grpc port of kms service.
+    #[clap(short = "k", long = "kms_port")]
+    kms_port: Option<String>,
+    /// Sets grpc port of controller service.
+    #[clap(short = "c", long = "controller_port")]
+    controller_port: Option<String>,
+    /// Sets path of a TOML/YAML config file with ports, chain_id and
+    /// signing parameters. CLI flags take precedence over the file.
+    #[clap(long = "config")]
+    config: Option<String>,
+    /// Total number of transactions to send.
+    #[clap(long = "count", default_value = "1000")]
+    count: u64,
+    /// Number of in-flight requests.
+    #[clap(long = "concurrency", default_value = "10")]
+    concurrency: usize,
+    /// Stop early after this many seconds, even if `count` hasn't been reached.
+    #[clap(long = "duration")]
+    duration: Option<u64>,
+    /// Also wait for each tx to be included and measure end-to-end
+    /// confirmation latency, not just acceptance latency.
+    #[clap(long = "wait")]
+    wait: bool,
+    /// Number of further blocks to wait for on top of the including
+    /// block, only used with `--wait`.
+    #[clap(long = "confirmations", default_value = "0")]
+    confirmations: u64,
+    /// Give up waiting for a given tx's confirmation after this many
+    /// seconds.
+    #[clap(long = "timeout", default_value = "60")]
+    timeout: u64,
+}
+
+/// A subcommand for send
+#[derive(Clap)]
+struct SendOpts {
+    /// Sets grpc port of kms service.
+    #[clap(short = "k", long = "kms_port")]
+    kms_port: Option<String>,
     /// Sets grpc port of controller service.
-    #[clap(short = "c", long = "controller_port", default_value = "50004")]
-    controller_port: String,
+    #[clap(short = "c", long = "controller_port")]
+    controller_port: Option<String>,
+    /// Sets path of a TOML/YAML config file with ports, chain_id and
+    /// signing parameters. CLI flags take precedence over the file.
+    #[clap(long = "config")]
+    config: Option<String>,
+    /// Recipient address as hex. Ignored when `--tx-file` is given.
+    #[clap(long = "to")]
+    to: Option<String>,
+    /// Transfer value as hex, defaults to zero.
+    #[clap(long = "value")]
+    value: Option<String>,
+    /// Call data as hex, defaults to empty.
+    #[clap(long = "data")]
+    data: Option<String>,
+    /// Quota for the transaction, defaults to the configured quota.
+    #[clap(long = "quota")]
+    quota: Option<u64>,
+    /// Absolute valid_until_block, defaults to the current block number
+    /// plus the configured window.
+    #[clap(long = "valid-until-block")]
+    valid_until_block: Option<u64>,
+    /// Load the transaction fields from a JSON file instead of the flags
+    /// above.
+    #[clap(long = "tx-file")]
+    tx_file: Option<String>,
+    /// Selects how the transaction is signed: `kms` asks the kms service
+    /// to generate and hold the key, `local` signs in-process with a key
+    /// supplied via `--private-key`/`--keystore`.
+    #[clap(long = "signer", default_value = "kms")]
+    signer: String,
+    /// Hex-encoded secp256k1 private key, used when `--signer local`.
+    #[clap(long = "private-key")]
+    private_key: Option<String>,
+    /// Path to a file holding a hex-encoded private key, used when
+    /// `--signer local`.
+    #[clap(long = "keystore")]
+    keystore: Option<String>,
+    /// Poll the controller until the tx is included in a block (instead
+    /// of returning right after submission) and print a receipt.
+    #[clap(long = "wait")]
+    wait: bool,
+    /// Number of further blocks to wait for on top of the including
+    /// block, only used with `--wait`.
+    #[clap(long = "confirmations", default_value = "0")]
+    confirmations: u64,
+    /// Give up waiting after this many seconds.
+    #[clap(long = "timeout", default_value = "60")]
+    timeout: u64,
 }
 
 fn main() {
@@ -65,10 +176,18 @@ fn main() {
         SubCommand::Run(opts) => {
             // init log4rs
             log4rs::init_file("tools-log4rs.yaml", Default::default()).unwrap();
-            info!("grpc port of kms service: {}", opts.kms_port);
-            info!("grpc port of controller service: {}", opts.controller_port);
             run(opts);
         }
+        SubCommand::Bench(opts) => {
+            // init log4rs
+            log4rs::init_file("tools-log4rs.yaml", Default::default()).unwrap();
+            bench(opts);
+        }
+        SubCommand::Send(opts) => {
+            // init log4rs
+            log4rs::init_file("tools-log4rs.yaml", Default::default()).unwrap();
+            send(opts);
+        }
     }
 }
 
@@ -76,142 +195,132 @@ use cita_ng_proto::blockchain::{Transaction, UnverifiedTransaction, Witness};
 use cita_ng_proto::controller::{
     raw_transaction::Tx, rpc_service_client::RpcServiceClient, Flag, RawTransaction,
 };
-use cita_ng_proto::kms::{
-    kms_service_client::KmsServiceClient, GenerateKeyPairRequest, HashDataRequest,
-    SignMessageRequest,
-};
+use cita_ng_proto::kms::{kms_service_client::KmsServiceClient, GenerateKeyPairRequest};
 use prost::Message;
 use tonic::Request;
 
-fn build_tx(start_block_number: u64) -> Transaction {
+fn build_tx(start_block_number: u64, params: &TxParams) -> Transaction {
     Transaction {
-        version: 0,
+        version: params.version,
         to: vec![1u8; 21],
         nonce: "test".to_owned(),
-        quota: 300_000,
-        valid_until_block: start_block_number + 80,
+        quota: params.quota,
+        valid_until_block: start_block_number + params.valid_until_block_window,
         data: vec![],
         value: vec![0u8; 32],
-        chain_id: vec![0u8; 32],
+        chain_id: params.chain_id.clone(),
     }
 }
 
-fn invalid_version_tx(start_block_number: u64) -> Transaction {
+fn invalid_version_tx(start_block_number: u64, params: &TxParams) -> Transaction {
     Transaction {
-        version: 1,
+        version: params.version + 1,
         to: vec![1u8; 21],
         nonce: "test".to_owned(),
-        quota: 300_000,
-        valid_until_block: start_block_number + 80,
+        quota: params.quota,
+        valid_until_block: start_block_number + params.valid_until_block_window,
         data: vec![],
         value: vec![0u8; 32],
-        chain_id: vec![0u8; 32],
+        chain_id: params.chain_id.clone(),
     }
 }
 
-fn invalid_nonce_tx(start_block_number: u64) -> Transaction {
+fn invalid_nonce_tx(start_block_number: u64, params: &TxParams) -> Transaction {
     Transaction {
-        version: 0,
+        version: params.version,
         to: vec![1u8; 21],
         nonce: "1testtesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttesttest".to_owned(),
-        quota: 300_000,
-        valid_until_block: start_block_number + 80,
+        quota: params.quota,
+        valid_until_block: start_block_number + params.valid_until_block_window,
         data: vec![],
         value: vec![0u8; 32],
-        chain_id: vec![0u8; 32],
+        chain_id: params.chain_id.clone(),
     }
 }
 
-fn invalid_vub_tx1(start_block_number: u64) -> Transaction {
+fn invalid_vub_tx1(start_block_number: u64, params: &TxParams) -> Transaction {
     Transaction {
-        version: 0,
+        version: params.version,
         to: vec![1u8; 21],
         nonce: "test".to_owned(),
-        quota: 300_000,
+        quota: params.quota,
         valid_until_block: start_block_number,
         data: vec![],
         value: vec![0u8; 32],
-        chain_id: vec![0u8; 32],
+        chain_id: params.chain_id.clone(),
     }
 }
 
-fn invalid_vub_tx2(start_block_number: u64) -> Transaction {
+fn invalid_vub_tx2(start_block_number: u64, params: &TxParams) -> Transaction {
     Transaction {
-        version: 0,
+        version: params.version,
         to: vec![1u8; 21],
         nonce: "test".to_owned(),
-        quota: 300_000,
+        quota: params.quota,
         valid_until_block: start_block_number + 200,
         data: vec![],
         value: vec![0u8; 32],
-        chain_id: vec![0u8; 32],
+        chain_id: params.chain_id.clone(),
     }
 }
 
-fn invalid_value_tx(start_block_number: u64) -> Transaction {
+fn invalid_value_tx(start_block_number: u64, params: &TxParams) -> Transaction {
     Transaction {
-        version: 0,
+        version: params.version,
         to: vec![1u8; 21],
         nonce: "test".to_owned(),
-        quota: 300_000,
-        valid_until_block: start_block_number + 80,
+        quota: params.quota,
+        valid_until_block: start_block_number + params.valid_until_block_window,
         data: vec![],
         value: vec![0u8; 31],
-        chain_id: vec![0u8; 32],
+        chain_id: params.chain_id.clone(),
     }
 }
 
-fn invalid_chain_id_tx(start_block_number: u64) -> Transaction {
+fn invalid_chain_id_tx(start_block_number: u64, params: &TxParams) -> Transaction {
     Transaction {
-        version: 0,
+        version: params.version,
         to: vec![1u8; 21],
         nonce: "test".to_owned(),
-        quota: 300_000,
-        valid_until_block: start_block_number + 80,
+        quota: params.quota,
+        valid_until_block: start_block_number + params.valid_until_block_window,
         data: vec![],
         value: vec![0u8; 32],
-        chain_id: vec![0u8; 31],
+        chain_id: params.chain_id[..params.chain_id.len() - 1].to_vec(),
     }
 }
 
-fn send_tx(
-    address: Vec<u8>,
-    key_id: u64,
-    kms_port: String,
-    controller_port: String,
-    tx: Transaction,
-) -> String {
-    let mut rt = Runtime::new().unwrap();
-
+/// Connect to the kms service.
+fn connect_kms(kms_port: &str, rt: &mut Runtime) -> KmsServiceClient<tonic::transport::Channel> {
     let kms_addr = format!("http://127.0.0.1:{}", kms_port);
-    let controller_addr = format!("http://127.0.0.1:{}", controller_port);
+    rt.block_on(KmsServiceClient::connect(kms_addr)).unwrap()
+}
 
-    let mut kms_client = rt.block_on(KmsServiceClient::connect(kms_addr)).unwrap();
-    let mut rpc_client = rt
-        .block_on(RpcServiceClient::connect(controller_addr))
-        .unwrap();
+/// Connect to the controller service.
+fn connect_controller(
+    controller_port: &str,
+    rt: &mut Runtime,
+) -> RpcServiceClient<tonic::transport::Channel> {
+    let controller_addr = format!("http://127.0.0.1:{}", controller_port);
+    rt.block_on(RpcServiceClient::connect(controller_addr))
+        .unwrap()
+}
 
-    // calc tx hash
+/// Hash and sign `tx` via `signer`, then submit it to the controller.
+/// Returns the tx hash reported back by the controller on success.
+async fn sign_and_submit(
+    signer: &mut dyn Signer,
+    rpc_client: &mut RpcServiceClient<tonic::transport::Channel>,
+    tx: Transaction,
+) -> Result<Vec<u8>, tonic::Status> {
     let mut tx_bytes = Vec::new();
     tx.encode(&mut tx_bytes).unwrap();
-    let request = HashDataRequest {
-        key_id,
-        data: tx_bytes,
-    };
-    let ret = rt.block_on(kms_client.hash_data(request)).unwrap();
-    let tx_hash = ret.into_inner().hash;
-
-    // sign tx hash
-    let request = Request::new(SignMessageRequest {
-        key_id,
-        msg: tx_hash.clone(),
-    });
-    let ret = rt.block_on(kms_client.sign_message(request)).unwrap();
-    let signature = ret.into_inner().signature;
+    let tx_hash = signer.hash(&tx_bytes).await;
+    let signature = signer.sign(&tx_hash).await;
 
     let witness = Witness {
         signature,
-        sender: address,
+        sender: signer.address(),
     };
 
     let unverified_tx = UnverifiedTransaction {
@@ -224,10 +333,18 @@ fn send_tx(
         tx: Some(Tx::NormalTx(unverified_tx)),
     };
 
-    let ret = rt.block_on(rpc_client.send_raw_transaction(raw_tx));
+    let response = rpc_client.send_raw_transaction(raw_tx).await?;
+    Ok(response.into_inner().hash)
+}
+
+fn send_tx(signer: &mut dyn Signer, controller_port: String, tx: Transaction) -> String {
+    let mut rt = Runtime::new().unwrap();
+    let mut rpc_client = connect_controller(&controller_port, &mut rt);
+
+    let ret = rt.block_on(sign_and_submit(signer, &mut rpc_client, tx));
     match ret {
-        Ok(response) => {
-            info!("tx hash {:?}", response.into_inner().hash);
+        Ok(hash) => {
+            info!("tx hash {:?}", hash);
             "".to_owned()
         }
         Err(status) => {
@@ -238,18 +355,24 @@ fn send_tx(
 }
 
 fn run(opts: RunOpts) {
-    let kms_port = opts.kms_port;
-    let controller_port = opts.controller_port;
+    let file_config: Option<Config> = opts.config.as_deref().map(load_config);
+    let kms_port = resolve(
+        opts.kms_port,
+        file_config.as_ref().and_then(|c| c.kms_port.clone()),
+        "50005",
+    );
+    let controller_port = resolve(
+        opts.controller_port,
+        file_config.as_ref().and_then(|c| c.controller_port.clone()),
+        "50004",
+    );
+    let params = TxParams::from_config(file_config.as_ref());
+    info!("grpc port of kms service: {}", kms_port);
+    info!("grpc port of controller service: {}", controller_port);
 
     let mut rt = Runtime::new().unwrap();
-
-    let kms_addr = format!("http://127.0.0.1:{}", kms_port.clone());
-    let controller_addr = format!("http://127.0.0.1:{}", controller_port.clone());
-
-    let mut kms_client = rt.block_on(KmsServiceClient::connect(kms_addr)).unwrap();
-    let mut rpc_client = rt
-        .block_on(RpcServiceClient::connect(controller_addr))
-        .unwrap();
+    let mut kms_client = connect_kms(&kms_port, &mut rt);
+    let mut rpc_client = connect_controller(&controller_port, &mut rt);
 
     // generate_key_pair for sign tx
     let request = Request::new(GenerateKeyPairRequest {
@@ -260,6 +383,7 @@ fn run(opts: RunOpts) {
     let response = ret.into_inner();
     let key_id = response.key_id;
     let address = response.address;
+    let mut signer = KmsSigner::new(kms_client, key_id, address);
 
     info!("key id is {}", key_id);
 
@@ -272,11 +396,9 @@ fn run(opts: RunOpts) {
     // ok
     assert_eq!(
         send_tx(
-            address.clone(),
-            key_id,
-            kms_port.clone(),
+            &mut signer,
             controller_port.clone(),
-            build_tx(start_block_number),
+            build_tx(start_block_number, &params),
         ),
         "".to_owned()
     );
@@ -284,78 +406,399 @@ fn run(opts: RunOpts) {
     // dup
     assert_eq!(
         send_tx(
-            address.clone(),
-            key_id,
-            kms_port.clone(),
+            &mut signer,
             controller_port.clone(),
-            build_tx(start_block_number),
+            build_tx(start_block_number, &params),
         ),
         "dup".to_owned()
     );
 
     assert_eq!(
         send_tx(
-            address.clone(),
-            key_id,
-            kms_port.clone(),
+            &mut signer,
             controller_port.clone(),
-            invalid_version_tx(start_block_number),
+            invalid_version_tx(start_block_number, &params),
         ),
         "Invalid version".to_owned()
     );
 
     assert_eq!(
         send_tx(
-            address.clone(),
-            key_id,
-            kms_port.clone(),
+            &mut signer,
             controller_port.clone(),
-            invalid_nonce_tx(start_block_number),
+            invalid_nonce_tx(start_block_number, &params),
         ),
         "Invalid nonce".to_owned()
     );
 
     assert_eq!(
         send_tx(
-            address.clone(),
-            key_id,
-            kms_port.clone(),
+            &mut signer,
             controller_port.clone(),
-            invalid_vub_tx1(start_block_number),
+            invalid_vub_tx1(start_block_number, &params),
         ),
         "Invalid valid_until_block".to_owned()
     );
 
     assert_eq!(
         send_tx(
-            address.clone(),
-            key_id,
-            kms_port.clone(),
+            &mut signer,
             controller_port.clone(),
-            invalid_vub_tx2(start_block_number),
+            invalid_vub_tx2(start_block_number, &params),
         ),
         "Invalid valid_until_block".to_owned()
     );
 
     assert_eq!(
         send_tx(
-            address.clone(),
-            key_id,
-            kms_port.clone(),
+            &mut signer,
             controller_port.clone(),
-            invalid_value_tx(start_block_number),
+            invalid_value_tx(start_block_number, &params),
         ),
         "Invalid value".to_owned()
     );
 
     assert_eq!(
         send_tx(
-            address.clone(),
-            key_id,
-            kms_port.clone(),
+            &mut signer,
             controller_port.clone(),
-            invalid_chain_id_tx(start_block_number),
+            invalid_chain_id_tx(start_block_number, &params),
         ),
         "Invalid chain_id".to_owned()
     );
 }
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+fn bench_tx(start_block_number: u64, nonce: u64, params: &TxParams) -> Transaction {
+    Transaction {
+        version: params.version,
+        to: vec![1u8; 21],
+        nonce: nonce.to_string(),
+        quota: params.quota,
+        valid_until_block: start_block_number + params.valid_until_block_window,
+        data: vec![],
+        value: vec![0u8; 32],
+        chain_id: params.chain_id.clone(),
+    }
+}
+
+/// One signed-and-submitted transaction's outcome, used to compute the
+/// aggregate throughput/latency report at the end of a bench run.
+struct BenchResult {
+    latency: Duration,
+    ok: bool,
+    /// End-to-end time until the tx was confirmed, only set with `--wait`.
+    confirm_latency: Option<Duration>,
+}
+
+async fn bench_send(
+    mut signer: KmsSigner,
+    mut rpc_client: RpcServiceClient<tonic::transport::Channel>,
+    tx: Transaction,
+    wait: Option<(u64, Duration)>,
+) -> BenchResult {
+    let started_at = Instant::now();
+    let ret = sign_and_submit(&mut signer, &mut rpc_client, tx).await;
+    let latency = started_at.elapsed();
+    let ok = ret.is_ok();
+
+    let confirm_latency = match (ret, wait) {
+        (Ok(tx_hash), Some((confirmations, timeout))) => {
+            wait_for_confirmation(&mut rpc_client, tx_hash, confirmations, Duration::from_secs(3), timeout)
+                .await
+                .map(|_| started_at.elapsed())
+        }
+        _ => None,
+    };
+
+    BenchResult {
+        latency,
+        ok,
+        confirm_latency,
+    }
+}
+
+fn percentile(sorted_latencies: &[Duration], p: f64) -> Duration {
+    if sorted_latencies.is_empty() {
+        return Duration::default();
+    }
+    let rank = ((sorted_latencies.len() as f64 - 1.0) * p).round() as usize;
+    sorted_latencies[rank]
+}
+
+fn bench(opts: BenchOpts) {
+    let file_config: Option<Config> = opts.config.as_deref().map(load_config);
+    let kms_port = resolve(
+        opts.kms_port,
+        file_config.as_ref().and_then(|c| c.kms_port.clone()),
+        "50005",
+    );
+    let controller_port = resolve(
+        opts.controller_port,
+        file_config.as_ref().and_then(|c| c.controller_port.clone()),
+        "50004",
+    );
+    let params = TxParams::from_config(file_config.as_ref());
+    let count = opts.count;
+    let concurrency = opts.concurrency;
+    if concurrency == 0 {
+        panic!("--concurrency must be at least 1");
+    }
+    let duration_cap = opts.duration.map(Duration::from_secs);
+    let wait = opts
+        .wait
+        .then(|| (opts.confirmations, Duration::from_secs(opts.timeout)));
+    info!("grpc port of kms service: {}", kms_port);
+    info!("grpc port of controller service: {}", controller_port);
+
+    let mut rt = Runtime::new().unwrap();
+    let mut kms_client = connect_kms(&kms_port, &mut rt);
+    let mut rpc_client = connect_controller(&controller_port, &mut rt);
+
+    // generate_key_pair once for all the bench transactions
+    let request = Request::new(GenerateKeyPairRequest {
+        crypt_type: 1,
+        description: "bench".to_owned(),
+    });
+    let ret = rt.block_on(kms_client.generate_key_pair(request)).unwrap();
+    let response = ret.into_inner();
+    let key_id = response.key_id;
+    let address = response.address;
+    info!("key id is {}", key_id);
+
+    let request = Request::new(Flag { flag: false });
+    let ret = rt.block_on(rpc_client.get_block_number(request)).unwrap();
+    let start_block_number = ret.into_inner().block_number;
+    info!("block_number is {} before bench", start_block_number);
+
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let bench_started_at = Instant::now();
+
+    let results = rt.block_on(async {
+        let mut handles = Vec::with_capacity(count as usize);
+        for nonce in 0..count {
+            if let Some(cap) = duration_cap {
+                if bench_started_at.elapsed() >= cap {
+                    break;
+                }
+            }
+            let semaphore = semaphore.clone();
+            let signer = KmsSigner::new(kms_client.clone(), key_id, address.clone());
+            let rpc_client = rpc_client.clone();
+            let tx = bench_tx(start_block_number, nonce, &params);
+            handles.push(tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await.unwrap();
+                bench_send(signer, rpc_client, tx, wait).await
+            }));
+        }
+
+        let mut results = Vec::with_capacity(handles.len());
+        for handle in handles {
+            let remaining = match duration_cap {
+                Some(cap) => match cap.checked_sub(bench_started_at.elapsed()) {
+                    Some(remaining) => remaining,
+                    None => {
+                        handle.abort();
+                        continue;
+                    }
+                },
+                None => {
+                    results.push(handle.await.unwrap());
+                    continue;
+                }
+            };
+            match tokio::time::timeout(remaining, handle).await {
+                Ok(joined) => results.push(joined.unwrap()),
+                Err(_) => break,
+            }
+        }
+        results
+    });
+
+    let total_elapsed = bench_started_at.elapsed();
+    let sent = results.len() as u64;
+    let succeeded = results.iter().filter(|r| r.ok).count() as u64;
+    let failed = sent - succeeded;
+
+    let mut confirm_latencies: Vec<Duration> =
+        results.iter().filter_map(|r| r.confirm_latency).collect();
+    confirm_latencies.sort();
+
+    let mut latencies: Vec<Duration> = results.into_iter().map(|r| r.latency).collect();
+    latencies.sort();
+
+    let tps = if total_elapsed.as_secs_f64() > 0.0 {
+        sent as f64 / total_elapsed.as_secs_f64()
+    } else {
+        0.0
+    };
+
+    println!("sent: {}, succeeded: {}, failed: {}", sent, succeeded, failed);
+    println!("elapsed: {:.3}s, tps: {:.2}", total_elapsed.as_secs_f64(), tps);
+    println!(
+        "latency p50: {:?}, p90: {:?}, p99: {:?}",
+        percentile(&latencies, 0.50),
+        percentile(&latencies, 0.90),
+        percentile(&latencies, 0.99),
+    );
+    if wait.is_some() {
+        println!(
+            "confirmed: {}, confirm latency p50: {:?}, p90: {:?}, p99: {:?}",
+            confirm_latencies.len(),
+            percentile(&confirm_latencies, 0.50),
+            percentile(&confirm_latencies, 0.90),
+            percentile(&confirm_latencies, 0.99),
+        );
+    }
+}
+
+use serde::Deserialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Transaction fields as loaded from a `--tx-file` JSON document. `to` is
+/// required; any other field left unset falls back to the configured
+/// default. The `send` transaction flags are not consulted.
+#[derive(Deserialize)]
+struct TxFile {
+    to: String,
+    value: Option<String>,
+    data: Option<String>,
+    quota: Option<u64>,
+    valid_until_block: Option<u64>,
+}
+
+fn decode_hex(s: &str) -> Vec<u8> {
+    hex::decode(s.trim_start_matches("0x")).expect("expected a hex string")
+}
+
+/// Nonces only need to be unique per sender, not random; a nanosecond
+/// timestamp is enough to avoid colliding with an earlier `send`.
+fn random_nonce() -> String {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos()
+        .to_string()
+}
+
+fn build_send_tx(opts: &SendOpts, start_block_number: u64, params: &TxParams) -> Transaction {
+    let (to, value, data, quota, valid_until_block) = match &opts.tx_file {
+        Some(path) => {
+            let content = std::fs::read_to_string(path)
+                .unwrap_or_else(|e| panic!("failed to read tx file {}: {}", path, e));
+            let tx_file: TxFile = serde_json::from_str(&content)
+                .unwrap_or_else(|e| panic!("failed to parse tx file {}: {}", path, e));
+            (
+                tx_file.to,
+                tx_file.value,
+                tx_file.data,
+                tx_file.quota,
+                tx_file.valid_until_block,
+            )
+        }
+        None => (
+            opts.to.clone().expect("--to or --tx-file is required"),
+            opts.value.clone(),
+            opts.data.clone(),
+            opts.quota,
+            opts.valid_until_block,
+        ),
+    };
+
+    Transaction {
+        version: params.version,
+        to: decode_hex(&to),
+        nonce: random_nonce(),
+        quota: quota.unwrap_or(params.quota),
+        valid_until_block: valid_until_block
+            .unwrap_or(start_block_number + params.valid_until_block_window),
+        data: data.as_deref().map(decode_hex).unwrap_or_default(),
+        value: value.as_deref().map(decode_hex).unwrap_or_else(|| vec![0u8; 32]),
+        chain_id: params.chain_id.clone(),
+    }
+}
+
+/// Build the signer requested by `--signer`, connecting to kms and
+/// generating a key pair for `kms` mode, or loading a local key for
+/// `local` mode.
+fn build_signer(opts: &SendOpts, kms_port: &str, rt: &mut Runtime) -> Box<dyn Signer> {
+    match opts.signer.as_str() {
+        "kms" => {
+            let mut kms_client = connect_kms(kms_port, rt);
+            let request = Request::new(GenerateKeyPairRequest {
+                crypt_type: 1,
+                description: "send".to_owned(),
+            });
+            let ret = rt.block_on(kms_client.generate_key_pair(request)).unwrap();
+            let response = ret.into_inner();
+            Box::new(KmsSigner::new(kms_client, response.key_id, response.address))
+        }
+        "local" => {
+            if let Some(private_key) = &opts.private_key {
+                Box::new(LocalSigner::from_private_key(&decode_hex(private_key)))
+            } else if let Some(keystore) = &opts.keystore {
+                Box::new(LocalSigner::from_keystore(keystore))
+            } else {
+                panic!("--signer local requires --private-key or --keystore");
+            }
+        }
+        other => panic!("unknown signer {}, expected kms or local", other),
+    }
+}
+
+fn send(opts: SendOpts) {
+    let file_config: Option<Config> = opts.config.as_deref().map(load_config);
+    let kms_port = resolve(
+        opts.kms_port.clone(),
+        file_config.as_ref().and_then(|c| c.kms_port.clone()),
+        "50005",
+    );
+    let controller_port = resolve(
+        opts.controller_port.clone(),
+        file_config.as_ref().and_then(|c| c.controller_port.clone()),
+        "50004",
+    );
+    let params = TxParams::from_config(file_config.as_ref());
+    info!("grpc port of kms service: {}", kms_port);
+    info!("grpc port of controller service: {}", controller_port);
+
+    let mut rt = Runtime::new().unwrap();
+    let mut signer = build_signer(&opts, &kms_port, &mut rt);
+    let mut rpc_client = connect_controller(&controller_port, &mut rt);
+
+    let request = Request::new(Flag { flag: false });
+    let ret = rt.block_on(rpc_client.get_block_number(request)).unwrap();
+    let start_block_number = ret.into_inner().block_number;
+
+    let tx = build_send_tx(&opts, start_block_number, &params);
+
+    let ret = rt.block_on(sign_and_submit(signer.as_mut(), &mut rpc_client, tx));
+    let tx_hash = match ret {
+        Ok(hash) => hash,
+        Err(status) => {
+            eprintln!("error: {}", status.message());
+            std::process::exit(1);
+        }
+    };
+    println!("{}", hex::encode(&tx_hash));
+
+    if opts.wait {
+        let receipt = rt.block_on(wait_for_confirmation(
+            &mut rpc_client,
+            tx_hash,
+            opts.confirmations,
+            Duration::from_secs(3),
+            Duration::from_secs(opts.timeout),
+        ));
+        match receipt {
+            Some(receipt) => println!("included in block {}", receipt.block_number),
+            None => {
+                eprintln!("timed out waiting for confirmation");
+                std::process::exit(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright Rivtower Technologies LLC.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use cita_ng_proto::kms::{kms_service_client::KmsServiceClient, HashDataRequest, SignMessageRequest};
+use sha3::{Digest, Keccak256};
+use tonic::transport::Channel;
+use tonic::Request;
+
+/// Everything needed to hash and sign a transaction. Implementations may
+/// reach out to a remote service (`KmsSigner`) or hold the key material
+/// in-process (`LocalSigner`) - `build_tx`/`send_tx` only depend on this
+/// trait, so callers can swap signing backends without touching them.
+#[async_trait::async_trait]
+pub trait Signer: Send {
+    async fn hash(&mut self, data: &[u8]) -> Vec<u8>;
+    async fn sign(&mut self, msg: &[u8]) -> Vec<u8>;
+    fn address(&self) -> Vec<u8>;
+}
+
+/// Signs by delegating to the kms service, which both generated and
+/// holds the private key.
+pub struct KmsSigner {
+    client: KmsServiceClient<Channel>,
+    key_id: u64,
+    address: Vec<u8>,
+}
+
+impl KmsSigner {
+    pub fn new(client: KmsServiceClient<Channel>, key_id: u64, address: Vec<u8>) -> Self {
+        KmsSigner {
+            client,
+            key_id,
+            address,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for KmsSigner {
+    async fn hash(&mut self, data: &[u8]) -> Vec<u8> {
+        let request = HashDataRequest {
+            key_id: self.key_id,
+            data: data.to_vec(),
+        };
+        self.client.hash_data(request).await.unwrap().into_inner().hash
+    }
+
+    async fn sign(&mut self, msg: &[u8]) -> Vec<u8> {
+        let request = Request::new(SignMessageRequest {
+            key_id: self.key_id,
+            msg: msg.to_vec(),
+        });
+        self.client
+            .sign_message(request)
+            .await
+            .unwrap()
+            .into_inner()
+            .signature
+    }
+
+    fn address(&self) -> Vec<u8> {
+        self.address.clone()
+    }
+}
+
+/// Signs offline with a secp256k1 private key held in memory, so
+/// transactions can be built and signed on a machine that never talks to
+/// kms.
+pub struct LocalSigner {
+    secp: secp256k1::Secp256k1<secp256k1::All>,
+    secret_key: secp256k1::SecretKey,
+    public_key: secp256k1::PublicKey,
+}
+
+impl LocalSigner {
+    pub fn from_private_key(private_key: &[u8]) -> Self {
+        let secp = secp256k1::Secp256k1::new();
+        let secret_key =
+            secp256k1::SecretKey::from_slice(private_key).expect("invalid private key");
+        let public_key = secp256k1::PublicKey::from_secret_key(&secp, &secret_key);
+        LocalSigner {
+            secp,
+            secret_key,
+            public_key,
+        }
+    }
+
+    pub fn from_keystore(path: &str) -> Self {
+        let content = std::fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("failed to read keystore {}: {}", path, e));
+        let private_key = hex::decode(content.trim().trim_start_matches("0x"))
+            .expect("keystore does not contain a valid hex private key");
+        Self::from_private_key(&private_key)
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for LocalSigner {
+    async fn hash(&mut self, data: &[u8]) -> Vec<u8> {
+        Keccak256::digest(data).to_vec()
+    }
+
+    async fn sign(&mut self, msg: &[u8]) -> Vec<u8> {
+        let message = secp256k1::Message::from_slice(msg).expect("msg to sign must be 32 bytes");
+        let (recovery_id, signature) = self
+            .secp
+            .sign_ecdsa_recoverable(&message, &self.secret_key)
+            .serialize_compact();
+        let mut bytes = signature.to_vec();
+        bytes.push(recovery_id.to_i32() as u8);
+        bytes
+    }
+
+    fn address(&self) -> Vec<u8> {
+        // Same scheme as Ethereum: the low 20 bytes of the keccak256 hash
+        // of the uncompressed public key (dropping its 0x04 prefix).
+        let uncompressed = self.public_key.serialize_uncompressed();
+        Keccak256::digest(&uncompressed[1..])[12..].to_vec()
+    }
+}